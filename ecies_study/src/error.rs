@@ -0,0 +1,122 @@
+use std::fmt;
+
+/// Errors that can occur while parsing the wire format of [`crate::EncryptedMessageInfo`].
+#[derive(Debug)]
+pub enum WireError {
+    /// The buffer ended before a field that was expected could be read.
+    Truncated,
+    /// The leading version byte did not match any version this crate understands.
+    UnknownVersion(u8),
+    /// A SEC1 point failed curve validation (not on the curve, not canonical, etc).
+    InvalidPoint,
+    /// The trailing signature bytes did not parse as a valid ECDSA signature.
+    InvalidSignature,
+}
+
+impl fmt::Display for WireError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WireError::Truncated => write!(f, "wire buffer truncated"),
+            WireError::UnknownVersion(v) => write!(f, "unknown wire version: {}", v),
+            WireError::InvalidPoint => write!(f, "invalid SEC1 public key point"),
+            WireError::InvalidSignature => write!(f, "invalid ECDSA signature encoding"),
+        }
+    }
+}
+
+impl std::error::Error for WireError {}
+
+/// Errors that can occur while decrypting an [`crate::EncryptedMessageInfo`].
+#[derive(Debug)]
+pub enum DecryptError {
+    /// The message's timestamp fell outside the allowed freshness window, so it
+    /// was rejected as either a replay or a badly-skewed clock.
+    Stale,
+    /// The framed message's HMAC tag did not match, so some field (timestamp,
+    /// nonce, the sender's ephemeral key, or the ciphertext) was altered in transit.
+    Tampered,
+    /// The AES-GCM tag did not verify; the ciphertext is corrupt or was
+    /// encrypted/decrypted with a mismatched key.
+    Aead,
+    /// The public key recovered from the message's recoverable signature did
+    /// not match the caller's expected/whitelisted sender.
+    UnexpectedSender,
+    /// The secret key passed to decrypt doesn't match the `receiver_pubkey`
+    /// supplied to `EncryptedMessageInfo::deserialize`: caller error (wrong
+    /// key pair), not tampering in transit.
+    WrongReceiver,
+}
+
+impl fmt::Display for DecryptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecryptError::Stale => write!(f, "message timestamp outside freshness window"),
+            DecryptError::Tampered => write!(f, "HMAC tag mismatch, message was tampered with"),
+            DecryptError::Aead => write!(f, "AES-GCM authentication failed"),
+            DecryptError::UnexpectedSender => write!(f, "recovered signer does not match expected sender"),
+            DecryptError::WrongReceiver => write!(f, "secret key does not match the deserialized receiver_pubkey"),
+        }
+    }
+}
+
+impl std::error::Error for DecryptError {}
+
+/// Errors that can occur while encrypting/decrypting a [`crate::keystore::KeystoreJson`].
+#[derive(Debug)]
+pub enum KeystoreError {
+    /// The scrypt cost parameters (N, r, p) in the record are invalid (e.g.
+    /// `r` or `p` is zero, or the combination overflows memory limits).
+    InvalidParams,
+    /// AES-GCM authentication failed: either the passphrase was wrong, or the
+    /// record was corrupted/tampered with after encryption.
+    DecryptionFailed,
+    /// The decrypted bytes don't form a valid secp256k1 scalar.
+    InvalidSecretKey,
+}
+
+impl fmt::Display for KeystoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeystoreError::InvalidParams => write!(f, "invalid scrypt parameters"),
+            KeystoreError::DecryptionFailed => write!(f, "wrong passphrase or corrupted keystore"),
+            KeystoreError::InvalidSecretKey => write!(f, "decrypted bytes are not a valid secp256k1 secret key"),
+        }
+    }
+}
+
+impl std::error::Error for KeystoreError {}
+
+/// Errors that can occur while handshaking or exchanging records over a
+/// [`crate::session::Session`].
+#[derive(Debug)]
+pub enum SessionError {
+    /// The underlying transport returned an I/O error.
+    Io(std::io::Error),
+    /// The handshake failed: a malformed ephemeral key or signature, or the
+    /// peer's recovered identity key didn't match the expected one.
+    Handshake,
+    /// AES-GCM encryption of an outgoing record failed.
+    Encrypt,
+    /// AES-GCM authentication of an incoming record failed; it was corrupted,
+    /// reordered, or the session is desynchronized.
+    Decrypt,
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionError::Io(err) => write!(f, "session transport I/O error: {}", err),
+            SessionError::Handshake => write!(f, "session handshake failed"),
+            SessionError::Encrypt => write!(f, "failed to encrypt outgoing record"),
+            SessionError::Decrypt => write!(f, "failed to authenticate incoming record"),
+        }
+    }
+}
+
+impl std::error::Error for SessionError {}
+
+impl From<std::io::Error> for SessionError {
+    fn from(err: std::io::Error) -> Self {
+        SessionError::Io(err)
+    }
+}
@@ -0,0 +1,45 @@
+//! Post-quantum hybrid encapsulation, gated behind the `pq-hybrid` feature.
+//!
+//! Combines the crate's classical secp256k1 ECDH with an ML-KEM-768
+//! encapsulation so messages stay confidential even if one of the two
+//! primitives is later broken. Nothing here is reachable unless `pq-hybrid`
+//! is enabled; the default build only ever sees the classical ECDH path.
+
+use kem::{Decapsulate, Encapsulate};
+use ml_kem::kem::{DecapsulationKey, EncapsulationKey};
+use ml_kem::{Ciphertext, KemCore, MlKem768, MlKem768Params};
+
+use rand_core::OsRng;
+
+/// A generated ML-KEM-768 keypair: the public encapsulation key handed out to
+/// senders, and the secret decapsulation key kept by the recipient.
+pub struct KemKeypair {
+    pub encapsulation_key: EncapsulationKey<MlKem768Params>,
+    pub decapsulation_key: DecapsulationKey<MlKem768Params>,
+}
+
+impl KemKeypair {
+    pub fn generate() -> Self {
+        let (decapsulation_key, encapsulation_key) = MlKem768::generate(&mut OsRng);
+        KemKeypair { encapsulation_key, decapsulation_key }
+    }
+}
+
+/// Encapsulate against a recipient's ML-KEM public key, returning the wire
+/// ciphertext alongside the 32-byte shared secret to mix into the HKDF IKM.
+pub fn encapsulate(encapsulation_key: &EncapsulationKey<MlKem768Params>) -> (Vec<u8>, [u8; 32]) {
+    let (ciphertext, shared_secret) = encapsulation_key
+        .encapsulate(&mut OsRng)
+        .expect("ML-KEM-768 encapsulation failure");
+
+    (ciphertext.to_vec(), shared_secret.into())
+}
+
+/// Decapsulate a wire ciphertext with the recipient's ML-KEM secret key,
+/// recovering the same 32-byte shared secret the sender derived.
+pub fn decapsulate(decapsulation_key: &DecapsulationKey<MlKem768Params>, ciphertext: &[u8]) -> Option<[u8; 32]> {
+    let ciphertext = Ciphertext::<MlKem768>::try_from(ciphertext).ok()?;
+    let shared_secret = decapsulation_key.decapsulate(&ciphertext).ok()?;
+
+    Some(shared_secret.into())
+}
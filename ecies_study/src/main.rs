@@ -1,17 +1,62 @@
 use elliptic_curve::ecdh::{diffie_hellman, SharedSecret};
-use elliptic_curve::{SecretKey, PublicKey};
+use elliptic_curve::{sec1::ToEncodedPoint, SecretKey, PublicKey};
 
 use k256::Secp256k1;
-use k256::ecdsa::{Signature, VerifyingKey, SigningKey, signature::{Signer, Verifier}};
+use k256::ecdsa::{Signature, VerifyingKey, SigningKey, RecoveryId};
 
 use rand_core::OsRng;
 
-use aes_gcm::{KeyInit,Aes256Gcm, Nonce, Error};
+use aes_gcm::{KeyInit,Aes256Gcm, Nonce};
 use aes_gcm::aead::{Aead, AeadCore, generic_array::{GenericArray, typenum::U12}};
 
 use hkdf::Hkdf;
 use sha2::Sha256;
 
+use hmac::{Hmac, Mac};
+
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+mod error;
+use error::{DecryptError, WireError};
+
+#[cfg(feature = "pq-hybrid")]
+mod hybrid;
+
+mod keystore;
+
+#[cfg(feature = "async-session")]
+mod session;
+
+type HmacSha256 = Hmac<Sha256>;
+
+// Wire format version tag for `EncryptedMessageInfo::serialize`/`deserialize`.
+// Bump this whenever the on-wire layout changes so old/new peers fail loudly
+// instead of silently misparsing each other's frames.
+const WIRE_VERSION: u8 = 4;
+
+// SEC1 compressed point size for secp256k1 (1-byte tag + 32-byte x-coordinate).
+const SEC1_COMPRESSED_LEN: usize = 33;
+
+// Fixed-size encoding of a (r, s) ECDSA signature over secp256k1.
+const SIGNATURE_LEN: usize = 64;
+
+// 8-byte big-endian Unix-millisecond timestamp.
+const TIMESTAMP_LEN: usize = 8;
+
+// HMAC-SHA256 authentication tag length.
+const HMAC_TAG_LEN: usize = 32;
+
+// Recoverable-signature recovery id ("v" in Ethereum-style terms): one byte,
+// carried alongside the (r, s) signature so the signer's public key can be
+// recovered instead of shipped on the wire.
+const RECOVERY_ID_LEN: usize = 1;
+
+// Default replay-protection window: a message is accepted only if its
+// timestamp is within this many milliseconds of the receiver's clock.
+const DEFAULT_FRESHNESS_WINDOW_MS: u64 = 30_000;
+
 
 
 fn main() {
@@ -20,8 +65,8 @@ fn main() {
     let (pubkey_b, secret_key_b) = generate_keypair();
 
     // Compute shared ECDH secret between parties a and b
-    let shared_a = compute_shared_secret(pubkey_b, secret_key_a);
-    let shared_b = compute_shared_secret(pubkey_a, secret_key_b);
+    let shared_a = compute_shared_secret(pubkey_b, &secret_key_a);
+    let shared_b = compute_shared_secret(pubkey_a, &secret_key_b);
 
     // Make sure the shared secret is equivalent from both perspectives
     assert!(shared_a.raw_secret_bytes() == shared_b.raw_secret_bytes());
@@ -29,9 +74,10 @@ fn main() {
     println!("raw_shared_bytes_a: {:?}\n\n raw_shared_bytes_b:{:?}", shared_a.raw_secret_bytes(), shared_b.raw_secret_bytes());
 
     // Generate HKDF/SHA256 cipher + nonce
-    // In production, nonce should be based on an incremental message counter 
+    // In production, nonce should be based on an incremental message counter
     // Probablility of repeating nonce is still incredibly low though
-    let (cipher, nonce) = generate_cipher(shared_a);
+    let info = build_cipher_info(&pubkey_a, &pubkey_b, None);
+    let (cipher, nonce) = generate_cipher(shared_a.raw_secret_bytes(), &info);
 
 
     // milady 
@@ -43,7 +89,7 @@ fn main() {
     // cipher would be able to be recomputed for decryption on receiver side using 
     // senders ephemeral pubkey, their own secretkey, and the nonce used to encrypt
     // the message on the sender side (passed on with ciphertext along wit ephemeral pubkey by sender)
-    let encrypted_message = encrypt_message_with_cipher(&message, &cipher, &nonce);
+    let encrypted_message = encrypt_message_with_cipher(message, &cipher, &nonce);
 
     println!("\nencrypted message:\n{:?}", encrypted_message);
 
@@ -57,34 +103,137 @@ fn main() {
     
 }
 
-// Generate keypair helper function 
-fn generate_keypair() -> (PublicKey<Secp256k1>, SecretKey<Secp256k1>) {
+// Owns 32 bytes of secret key material (HKDF output keying material, a raw
+// scalar, ...) and guarantees those bytes are wiped as soon as the value is
+// dropped, instead of lingering in freed memory.
+#[derive(Zeroize, ZeroizeOnDrop)]
+pub(crate) struct SecretBytes(pub(crate) [u8; 32]);
+
+impl std::ops::Deref for SecretBytes {
+    type Target = [u8; 32];
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl std::ops::DerefMut for SecretBytes {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+// Wraps a secp256k1 keypair so the secret scalar is explicitly wiped once the
+// keypair is dropped, giving this study code the same "no secret data lying
+// around" guarantee that motivated zero-on-drop secret keys elsewhere.
+#[derive(ZeroizeOnDrop)]
+struct Keypair {
+    #[zeroize(skip)]
+    public: PublicKey<Secp256k1>,
+    secret: SecretKey<Secp256k1>,
+}
+
+impl Keypair {
+    fn generate() -> Self {
+        let secret = SecretKey::<Secp256k1>::random(&mut OsRng);
+        let public = secret.public_key();
 
-    let secret = SecretKey::<Secp256k1>::random(&mut OsRng);
+        Keypair { public, secret }
+    }
 
-    let public = secret.public_key();
+    fn public_key(&self) -> PublicKey<Secp256k1> {
+        self.public
+    }
 
-    (public, secret)
+    fn secret_key(&self) -> &SecretKey<Secp256k1> {
+        &self.secret
+    }
+}
+
+// Generate keypair helper function. Convenience wrapper for long-lived/test
+// keys where handing back a bare `SecretKey` is fine. Code paths that generate
+// a short-lived *ephemeral* key (e.g. `send_message_inner`, `Session::handshake`)
+// should call `Keypair::generate()` directly and keep the `Keypair` itself alive
+// instead: the clone this function returns only has `keypair`'s own
+// zeroize-on-drop guarantee for as long as `keypair` exists, not after.
+fn generate_keypair() -> (PublicKey<Secp256k1>, SecretKey<Secp256k1>) {
+    let keypair = Keypair::generate();
 
+    (keypair.public_key(), keypair.secret_key().clone())
 }
 
-// Compute ECDH shared secret between a receivers pubkey and the senders secretkey
-fn compute_shared_secret(pubkey: PublicKey<Secp256k1>, secret: SecretKey<Secp256k1>) -> SharedSecret<Secp256k1> {
+// Compute ECDH shared secret between a receivers pubkey and the senders secretkey.
+// Takes `secret` by reference so callers can keep their own secret wrapped in a
+// zeroize-on-drop guard (e.g. `Keypair`) for its whole lifetime instead of handing
+// over ownership, which would otherwise put it at the mercy of whatever (if
+// anything) the caller's value does on drop.
+fn compute_shared_secret(pubkey: PublicKey<Secp256k1>, secret: &SecretKey<Secp256k1>) -> SharedSecret<Secp256k1> {
     let shared_secret = diffie_hellman(secret.to_nonzero_scalar(), pubkey.as_affine());
     shared_secret
 }
 
-// Compute AES-GCM cipher and nonce using the shared secret obtained from ECDH 
-// ECDH shared secret should not be used directly for encryption
-fn generate_cipher(diffie_secret: SharedSecret<Secp256k1>) -> (Aes256Gcm, Nonce<U12>) {
-    let hkdf = Hkdf::<Sha256>::new(None, &diffie_secret.raw_secret_bytes());
+// Domain-separation salt for the HKDF extract step. Fixed and public (salts don't
+// need to be secret), it just keeps this protocol's keys out of any other HKDF
+// usage that happens to share the same ECDH secret.
+const HKDF_SALT: &[u8] = b"ecies_study-hkdf-salt-v1";
+
+// Protocol label mixed into the HKDF `info` alongside the bound public keys.
+const HKDF_INFO_LABEL: &[u8] = b"ecies_study-aes256gcm-v1";
+
+// Build the HKDF `info` that binds the derived AES key to *who* the message is
+// between: the sender's ephemeral key, the recipient's key, and (in hybrid mode)
+// the ML-KEM ciphertext, so neither half of a hybrid encapsulation can be
+// substituted independently of the other. Without this, the same raw ECDH
+// secret could be replayed against a different (key, ciphertext) pairing
+// without detection (unknown-key-share / key-confusion).
+fn build_cipher_info(
+    sender_ephemeral: &PublicKey<Secp256k1>,
+    recipient: &PublicKey<Secp256k1>,
+    kem_ciphertext: Option<&[u8]>,
+) -> Vec<u8> {
+    let mut info = HKDF_INFO_LABEL.to_vec();
+    info.extend_from_slice(sender_ephemeral.to_encoded_point(true).as_bytes());
+    info.extend_from_slice(recipient.to_encoded_point(true).as_bytes());
+    if let Some(kem_ciphertext) = kem_ciphertext {
+        info.extend_from_slice(kem_ciphertext);
+    }
+    info
+}
 
-    let mut okm = [0u8; 32]; // Output keying material
-    hkdf.expand(&[], &mut okm).unwrap();
-    
+// Protocol label for the HMAC key, kept distinct from `HKDF_INFO_LABEL` so the
+// AES key and the HMAC key are independent even though they come from the same
+// HKDF extract step.
+const HKDF_HMAC_INFO_LABEL: &[u8] = b"ecies_study-hmac-sha256-v1";
+
+// Build the HKDF `info` for the HMAC key, bound to the same parties (and, in
+// hybrid mode, the same ML-KEM ciphertext) as the cipher key.
+fn build_hmac_info(
+    sender_ephemeral: &PublicKey<Secp256k1>,
+    recipient: &PublicKey<Secp256k1>,
+    kem_ciphertext: Option<&[u8]>,
+) -> Vec<u8> {
+    let mut info = HKDF_HMAC_INFO_LABEL.to_vec();
+    info.extend_from_slice(sender_ephemeral.to_encoded_point(true).as_bytes());
+    info.extend_from_slice(recipient.to_encoded_point(true).as_bytes());
+    if let Some(kem_ciphertext) = kem_ciphertext {
+        info.extend_from_slice(kem_ciphertext);
+    }
+    info
+}
+
+// Compute AES-GCM cipher and nonce from input keying material (`ikm`): the raw
+// ECDH secret alone in the classical path, or the ECDH secret concatenated with
+// an ML-KEM shared secret in hybrid mode (see `hybrid::encapsulate`).
+// The raw IKM should not be used directly for encryption.
+fn generate_cipher(ikm: &[u8], info: &[u8]) -> (Aes256Gcm, Nonce<U12>) {
+    let hkdf = Hkdf::<Sha256>::new(Some(HKDF_SALT), ikm);
 
-    let cipher_key = GenericArray::from_slice(&okm);
+    let mut okm = SecretBytes([0u8; 32]); // Output keying material
+    hkdf.expand(info, &mut okm.0).unwrap();
+
+    let cipher_key = GenericArray::from_slice(&*okm);
     let cipher = Aes256Gcm::new(cipher_key);
+    // `okm` is scrubbed here as it goes out of scope, now that the cipher has
+    // its own copy of the key schedule.
 
     let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
 
@@ -92,30 +241,76 @@ fn generate_cipher(diffie_secret: SharedSecret<Secp256k1>) -> (Aes256Gcm, Nonce<
 
 }
 
+// Derive the HMAC-SHA256 key used to authenticate the framed message, via a
+// second `expand` over the same HKDF extract, with a distinct `info` so it is
+// cryptographically independent of the AES key.
+fn derive_hmac_key(ikm: &[u8], info: &[u8]) -> SecretBytes {
+    let hkdf = Hkdf::<Sha256>::new(Some(HKDF_SALT), ikm);
+
+    let mut okm = SecretBytes([0u8; 32]);
+    hkdf.expand(info, &mut okm.0).unwrap();
+
+    okm
+}
+
+// Bytes covered by both `sign_recoverable` and the HMAC tag: everything a
+// receiver needs to have arrived intact before trusting the ciphertext is
+// worth opening. The sender's identity key is deliberately not framed here:
+// it's recovered from the signature itself (see `recover_pubkey`) instead of
+// being shipped alongside it.
+fn build_framed_bytes(
+    sender_ephemeral: &PublicKey<Secp256k1>,
+    nonce: &Nonce<U12>,
+    timestamp_ms: u64,
+    ciphertext: &[u8],
+) -> Vec<u8> {
+    let mut framed = Vec::with_capacity(SEC1_COMPRESSED_LEN + 12 + TIMESTAMP_LEN + ciphertext.len());
+    framed.extend_from_slice(sender_ephemeral.to_encoded_point(true).as_bytes());
+    framed.extend_from_slice(nonce.as_slice());
+    framed.extend_from_slice(&timestamp_ms.to_be_bytes());
+    framed.extend_from_slice(ciphertext);
+    framed
+}
+
+// Current time as Unix milliseconds, used to stamp outgoing messages and to
+// check incoming ones against the freshness window.
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system clock is before the Unix epoch")
+        .as_millis() as u64
+}
+
 // Helper function to encrypt message using AES-GCM cipher and nonce 
 fn encrypt_message_with_cipher(message: &str, cipher: &Aes256Gcm, nonce: &Nonce<U12>) -> Vec<u8> {
-    let ciphertext = cipher.encrypt(&nonce, message.as_bytes()).unwrap();
-    
+    let ciphertext = cipher.encrypt(nonce, message.as_bytes()).unwrap();
+
     ciphertext
 }
 
-// Decrypt ciphertext given computed AES-GCM symmetric secret 
+// Decrypt ciphertext given computed AES-GCM symmetric secret
 fn decrypt_message(nonce: &Nonce<U12>, cipher: &Aes256Gcm, ciphertext: &Vec<u8>) -> Vec<u8> {
-    let decrypted = cipher.decrypt(&nonce, ciphertext.as_ref());
+    let decrypted = cipher.decrypt(nonce, ciphertext.as_ref());
 
     decrypted.unwrap()
 }
 
-// Helper function to sign messages
-fn sign_message(secret_key: &SecretKey<Secp256k1>, message: &[u8]) -> Signature {
+// Sign `message` with a secp256k1 recoverable signature: the usual (r, s)
+// pair plus a recovery id, so the signer's public key can be recomputed from
+// (message, signature, recovery_id) alone instead of being shipped alongside it.
+fn sign_recoverable(secret_key: &SecretKey<Secp256k1>, message: &[u8]) -> (Signature, RecoveryId) {
     let signing_key = SigningKey::from(secret_key.clone());
-    signing_key.sign(message)
+    signing_key.sign_recoverable(message).expect("recoverable signing cannot fail")
 }
 
-// Helper funciton to verify signatures
-fn verify_signature(pubkey: &PublicKey<Secp256k1>, message: &[u8], signature: &Signature) -> bool {
-    let verifying_key = VerifyingKey::from(pubkey.clone());
-    verifying_key.verify(message, signature).is_ok()
+// Recover the public key that produced `signature`/`recovery_id` over `message`.
+// A tampered `message` (or a signature over a different message) simply
+// recovers a different, unrelated public key rather than erroring, so callers
+// that care who signed must compare the recovered key against an expected one.
+fn recover_pubkey(message: &[u8], signature: &Signature, recovery_id: RecoveryId) -> Result<PublicKey<Secp256k1>, WireError> {
+    let verifying_key = VerifyingKey::recover_from_msg(message, signature, recovery_id)
+        .map_err(|_| WireError::InvalidSignature)?;
+    Ok(PublicKey::from(verifying_key))
 }
 
 
@@ -125,57 +320,583 @@ pub struct EncryptedMessageInfo{
     receiver_pubkey: PublicKey<Secp256k1>,
     sender_ephemeral: PublicKey<Secp256k1>,
     nonce: Nonce<U12>,
-    sender_id_pubkey: PublicKey<Secp256k1>,
-    signature: Signature, 
+    signature: Signature,
+    // Recovery id for `signature`, over the framed message. Lets the receiver
+    // recompute the sender's identity public key with `recover_pubkey` instead
+    // of carrying it on the wire (see `decrypt_message_info_from`).
+    recovery_id: RecoveryId,
+    // Unix-millisecond send time, covered by `signature` and `hmac_tag`. Lets
+    // the receiver reject stale/replayed messages (see `decrypt_message_info_with_skew`).
+    timestamp_ms: u64,
+    // HMAC-SHA256 tag over `build_framed_bytes(..)`, keyed from a second HKDF
+    // expand distinct from the AES key. Catches tampering with fields (the
+    // timestamp and sender's ephemeral key) that AES-GCM's own tag never sees.
+    hmac_tag: [u8; 32],
+    // ML-KEM-768 encapsulation against the recipient's KEM public key, present
+    // only when the message was sent in hybrid mode (see `hybrid` module and
+    // `send_message_hybrid`). `None` means the classical ECDH-only path was used.
+    kem_ciphertext: Option<Vec<u8>>,
+}
+
+impl EncryptedMessageInfo {
+    // Canonical wire layout, all fields big-endian / fixed-width where possible so
+    // parsing never needs to guess at boundaries:
+    //
+    //   version(1) || sender_ephemeral(33) || nonce(12) || timestamp(8)
+    //     || kem_ciphertext_len(4, u32 BE) || kem_ciphertext(kem_ciphertext_len)
+    //     || ciphertext_len(4, u32 BE) || ciphertext(ciphertext_len) || hmac_tag(32)
+    //     || recovery_id(1) || signature(64)
+    //
+    // `kem_ciphertext_len` of 0 means the classical (non-hybrid) path was used;
+    // a real ML-KEM-768 ciphertext is never zero-length.
+    //
+    // `receiver_pubkey` is intentionally left off the wire: the recipient already
+    // knows their own key, so shipping it back to them would be redundant. The
+    // sender's identity key is likewise left off: `recovery_id` plus `signature`
+    // let the receiver recompute it (see `recover_pubkey`) instead.
+    pub fn serialize(&self) -> Vec<u8> {
+        let kem_ciphertext = self.kem_ciphertext.as_deref().unwrap_or(&[]);
+        let kem_ciphertext_len = kem_ciphertext.len() as u32;
+        let ciphertext_len = self.ciphertext.len() as u32;
+
+        let mut out = Vec::with_capacity(
+            1 + SEC1_COMPRESSED_LEN + 12 + TIMESTAMP_LEN + 4 + kem_ciphertext.len()
+                + 4 + self.ciphertext.len() + HMAC_TAG_LEN + RECOVERY_ID_LEN + SIGNATURE_LEN,
+        );
+
+        out.push(WIRE_VERSION);
+        out.extend_from_slice(self.sender_ephemeral.to_encoded_point(true).as_bytes());
+        out.extend_from_slice(self.nonce.as_slice());
+        out.extend_from_slice(&self.timestamp_ms.to_be_bytes());
+        out.extend_from_slice(&kem_ciphertext_len.to_be_bytes());
+        out.extend_from_slice(kem_ciphertext);
+        out.extend_from_slice(&ciphertext_len.to_be_bytes());
+        out.extend_from_slice(&self.ciphertext);
+        out.extend_from_slice(&self.hmac_tag);
+        out.push(self.recovery_id.to_byte());
+        out.extend_from_slice(&self.signature.to_bytes());
+
+        out
+    }
+
+    // Deserialize a frame produced by `serialize`. `receiver_pubkey` must be supplied
+    // by the caller since it is never written to the wire (see `serialize`).
+    pub fn deserialize(bytes: &[u8], receiver_pubkey: PublicKey<Secp256k1>) -> Result<Self, WireError> {
+        let mut cursor = 0usize;
+
+        let take = |cursor: &mut usize, len: usize| -> Result<&[u8], WireError> {
+            let slice = bytes.get(*cursor..*cursor + len).ok_or(WireError::Truncated)?;
+            *cursor += len;
+            Ok(slice)
+        };
+
+        let version = *take(&mut cursor, 1)?.first().ok_or(WireError::Truncated)?;
+        if version != WIRE_VERSION {
+            return Err(WireError::UnknownVersion(version));
+        }
+
+        let sender_ephemeral = PublicKey::<Secp256k1>::from_sec1_bytes(take(&mut cursor, SEC1_COMPRESSED_LEN)?)
+            .map_err(|_| WireError::InvalidPoint)?;
+
+        let nonce = *Nonce::<U12>::from_slice(take(&mut cursor, 12)?);
+
+        let timestamp_bytes = take(&mut cursor, TIMESTAMP_LEN)?;
+        let timestamp_ms = u64::from_be_bytes(timestamp_bytes.try_into().unwrap());
+
+        let kem_ciphertext_len_bytes = take(&mut cursor, 4)?;
+        let kem_ciphertext_len = u32::from_be_bytes(kem_ciphertext_len_bytes.try_into().unwrap()) as usize;
+        let kem_ciphertext = if kem_ciphertext_len == 0 {
+            None
+        } else {
+            Some(take(&mut cursor, kem_ciphertext_len)?.to_vec())
+        };
+
+        let ciphertext_len_bytes = take(&mut cursor, 4)?;
+        let ciphertext_len = u32::from_be_bytes(ciphertext_len_bytes.try_into().unwrap()) as usize;
+        let ciphertext = take(&mut cursor, ciphertext_len)?.to_vec();
+
+        let hmac_tag = take(&mut cursor, HMAC_TAG_LEN)?.try_into().unwrap();
+
+        let recovery_id_byte = *take(&mut cursor, RECOVERY_ID_LEN)?.first().ok_or(WireError::Truncated)?;
+        let recovery_id = RecoveryId::from_byte(recovery_id_byte).ok_or(WireError::InvalidSignature)?;
+
+        let signature = Signature::from_slice(take(&mut cursor, SIGNATURE_LEN)?)
+            .map_err(|_| WireError::InvalidSignature)?;
+
+        Ok(EncryptedMessageInfo {
+            ciphertext,
+            receiver_pubkey,
+            sender_ephemeral,
+            nonce,
+            signature,
+            recovery_id,
+            timestamp_ms,
+            hmac_tag,
+            kem_ciphertext,
+        })
+    }
 }
 
-// TODO: 
-// send_message will be used in future to send a message to another user (pubkey)
+// send_message sends a message to another user (pubkey)
 // this returns a EncryptedMessageInfo, which would contain all the information
 // necessary to decrypt the message on the receiving end
 //
 // ephemeral keys must be generated on a message-by-message bassis to preserve signature security.
 // users will have main keypair used to sign encrypted messages and prove identity/validity of message
 fn send_message(message: &str, from_pub: PublicKey<Secp256k1>, from_secret: SecretKey<Secp256k1>, to_pub: PublicKey<Secp256k1>) -> EncryptedMessageInfo {
+    send_message_at(message, from_pub, from_secret, to_pub, now_ms())
+}
 
+// Same as `send_message`, but with an explicit send timestamp instead of the
+// current system time. Exists so tests can construct messages with a known
+// timestamp without needing to sleep or mock the clock.
+fn send_message_at(
+    message: &str,
+    from_pub: PublicKey<Secp256k1>,
+    from_secret: SecretKey<Secp256k1>,
+    to_pub: PublicKey<Secp256k1>,
+    timestamp_ms: u64,
+) -> EncryptedMessageInfo {
+    send_message_inner(message, from_pub, from_secret, to_pub, timestamp_ms, None)
+}
 
-    let (eph_pub, eph_sec) = generate_keypair(); 
-    let shared_diffie = compute_shared_secret(to_pub, eph_sec);
+// Hybrid variant of `send_message`: in addition to the classical ephemeral
+// ECDH, encapsulates against the recipient's ML-KEM-768 public key and mixes
+// both shared secrets into the HKDF IKM, so confidentiality survives even if
+// one of the two primitives is later broken.
+#[cfg(feature = "pq-hybrid")]
+fn send_message_hybrid(
+    message: &str,
+    from_pub: PublicKey<Secp256k1>,
+    from_secret: SecretKey<Secp256k1>,
+    to_pub: PublicKey<Secp256k1>,
+    kem_encapsulation_key: &ml_kem::kem::EncapsulationKey<ml_kem::MlKem768Params>,
+) -> EncryptedMessageInfo {
+    let (kem_ciphertext, kem_shared_secret) = hybrid::encapsulate(kem_encapsulation_key);
+
+    send_message_inner(
+        message,
+        from_pub,
+        from_secret,
+        to_pub,
+        now_ms(),
+        Some((kem_ciphertext, kem_shared_secret)),
+    )
+}
 
-    let (cipher, nonce) = generate_cipher(shared_diffie);
+// Shared implementation behind `send_message`/`send_message_at`/`send_message_hybrid`.
+// `kem` carries the wire ciphertext and the shared secret to fold into the IKM
+// when sending in hybrid mode; `None` is the classical ECDH-only path.
+fn send_message_inner(
+    message: &str,
+    from_pub: PublicKey<Secp256k1>,
+    from_secret: SecretKey<Secp256k1>,
+    to_pub: PublicKey<Secp256k1>,
+    timestamp_ms: u64,
+    kem: Option<(Vec<u8>, [u8; 32])>,
+) -> EncryptedMessageInfo {
+
+    // Hold the ephemeral keypair itself (not a secret cloned out of it), so the
+    // scalar stays under `Keypair`'s zeroize-on-drop guarantee for its entire
+    // lifetime in this function, right up until `eph_keypair` drops at the end.
+    let eph_keypair = Keypair::generate();
+    let eph_pub = eph_keypair.public_key();
+    let shared_diffie = compute_shared_secret(to_pub, eph_keypair.secret_key());
+
+    let kem_ciphertext = kem.as_ref().map(|(ciphertext, _)| ciphertext.clone());
+
+    let mut ikm = shared_diffie.raw_secret_bytes().to_vec();
+    if let Some((_, kem_shared_secret)) = &kem {
+        ikm.extend_from_slice(kem_shared_secret);
+    }
+
+    let cipher_info = build_cipher_info(&eph_pub, &to_pub, kem_ciphertext.as_deref());
+    let (cipher, nonce) = generate_cipher(&ikm, &cipher_info);
 
-    let ciphertext = cipher.encrypt(&nonce, message.as_bytes());
+    let ciphertext = cipher.encrypt(&nonce, message.as_bytes()).unwrap();
 
-    let signing_key = SigningKey::from(&from_secret.clone());
+    let framed = build_framed_bytes(&eph_pub, &nonce, timestamp_ms, &ciphertext);
 
-    let signature = sign_message(&from_secret, &ciphertext.clone().unwrap());
+    let (signature, recovery_id) = sign_recoverable(&from_secret, &framed);
+    assert!(recover_pubkey(&framed, &signature, recovery_id).unwrap() == from_pub);
 
-    assert!(verify_signature(&from_pub, &ciphertext.clone().unwrap(), &signature));
+    let hmac_info = build_hmac_info(&eph_pub, &to_pub, kem_ciphertext.as_deref());
+    let hmac_key = derive_hmac_key(&ikm, &hmac_info);
+    let mut mac = <HmacSha256 as Mac>::new_from_slice(&*hmac_key).unwrap();
+    mac.update(&framed);
+    let hmac_tag: [u8; 32] = mac.finalize().into_bytes().into();
 
-    let encrypted_message_info = EncryptedMessageInfo {
-        ciphertext: ciphertext.clone().unwrap(),
+    EncryptedMessageInfo {
+        ciphertext,
         receiver_pubkey: to_pub,
         sender_ephemeral: eph_pub,
-        nonce: nonce,
-        sender_id_pubkey: from_pub,
-        signature: signature
-    };
-
-    encrypted_message_info
+        nonce,
+        signature,
+        recovery_id,
+        timestamp_ms,
+        hmac_tag,
+        kem_ciphertext,
+    }
 }
 
 
-// Using the information in a EncryptedMessageInfo + the secret key of the indended recipient of a message
-// decrypt ciphertext contained in the struct and return the decoded bytes
-fn decrypt_message_info(message_info: EncryptedMessageInfo, secret_key: SecretKey<Secp256k1>) -> Vec<u8> {
+// Using the information in a EncryptedMessageInfo + the secret key of the indended recipient of a message,
+// decrypt ciphertext contained in the struct and return the decoded bytes. Rejects messages whose
+// timestamp has drifted outside `DEFAULT_FRESHNESS_WINDOW_MS`; see `decrypt_message_info_with_skew`
+// for a configurable window.
+fn decrypt_message_info(message_info: EncryptedMessageInfo, secret_key: SecretKey<Secp256k1>) -> Result<Vec<u8>, DecryptError> {
+    decrypt_message_info_with_skew(message_info, secret_key, DEFAULT_FRESHNESS_WINDOW_MS)
+}
 
-    // alright, so first recompute the shared secret using senders epheremeral pubkey and your secret key
-    let shared_diffie = compute_shared_secret(message_info.sender_ephemeral, secret_key);
+// `decrypt_message_info`, but with a caller-supplied freshness window (allowed
+// clock skew, in milliseconds) instead of the default +/-30s.
+fn decrypt_message_info_with_skew(
+    message_info: EncryptedMessageInfo,
+    secret_key: SecretKey<Secp256k1>,
+    max_skew_ms: u64,
+) -> Result<Vec<u8>, DecryptError> {
+    decrypt_message_info_inner(message_info, secret_key, max_skew_ms, None, None)
+}
 
-    let (cipher, _) = generate_cipher(shared_diffie);
+// `decrypt_message_info`, but additionally recovers the signer's identity key
+// from the signature (the key itself is not on the wire) and rejects the message unless it
+// matches `expected_sender`, e.g. a pinned contact or whitelist entry.
+fn decrypt_message_info_from(
+    message_info: EncryptedMessageInfo,
+    secret_key: SecretKey<Secp256k1>,
+    expected_sender: PublicKey<Secp256k1>,
+) -> Result<Vec<u8>, DecryptError> {
+    decrypt_message_info_inner(message_info, secret_key, DEFAULT_FRESHNESS_WINDOW_MS, None, Some(expected_sender))
+}
 
-    let decrypted = cipher.decrypt(&message_info.nonce, message_info.ciphertext.as_ref());
+// Hybrid variant of `decrypt_message_info`: decapsulates the ML-KEM ciphertext
+// carried in the message with the recipient's KEM secret key, then mixes the
+// recovered shared secret into the IKM exactly as `send_message_hybrid` did.
+#[cfg(feature = "pq-hybrid")]
+fn decrypt_message_info_hybrid(
+    message_info: EncryptedMessageInfo,
+    secret_key: SecretKey<Secp256k1>,
+    kem_decapsulation_key: &ml_kem::kem::DecapsulationKey<ml_kem::MlKem768Params>,
+) -> Result<Vec<u8>, DecryptError> {
+    let kem_ciphertext = message_info.kem_ciphertext.as_deref().ok_or(DecryptError::Tampered)?;
+    let kem_shared_secret = hybrid::decapsulate(kem_decapsulation_key, kem_ciphertext)
+        .ok_or(DecryptError::Tampered)?;
+
+    decrypt_message_info_inner(message_info, secret_key, DEFAULT_FRESHNESS_WINDOW_MS, Some(kem_shared_secret), None)
+}
 
-    decrypted.unwrap()
+// Shared implementation behind `decrypt_message_info_with_skew`/`decrypt_message_info_from`/
+// `decrypt_message_info_hybrid`. `kem_shared_secret` is the already-decapsulated
+// ML-KEM shared secret in hybrid mode, or `None` on the classical path.
+// `expected_sender`, when set, is checked against the key recovered from the
+// message's signature before decryption proceeds.
+fn decrypt_message_info_inner(
+    message_info: EncryptedMessageInfo,
+    secret_key: SecretKey<Secp256k1>,
+    max_skew_ms: u64,
+    kem_shared_secret: Option<[u8; 32]>,
+    expected_sender: Option<PublicKey<Secp256k1>>,
+) -> Result<Vec<u8>, DecryptError> {
+
+    // alright, so first recompute the shared secret using senders epheremeral pubkey and your secret key
+    let receiver_pubkey = secret_key.public_key();
+
+    // `message_info.receiver_pubkey` is never shipped on the wire (see
+    // `EncryptedMessageInfo::serialize`); it's whatever the caller passed to
+    // `deserialize`. Check it actually matches the key we're decrypting with,
+    // so a caller that deserializes with one recipient's key and then decrypts
+    // with a different one's secret key gets a clear error instead of quietly
+    // proceeding with a meaningless stored value.
+    if receiver_pubkey != message_info.receiver_pubkey {
+        return Err(DecryptError::WrongReceiver);
+    }
+
+    let shared_diffie = compute_shared_secret(message_info.sender_ephemeral, &secret_key);
+
+    let mut ikm = shared_diffie.raw_secret_bytes().to_vec();
+    if let Some(kem_shared_secret) = &kem_shared_secret {
+        ikm.extend_from_slice(kem_shared_secret);
+    }
+
+    // Check the HMAC tag before the signature, timestamp and AES-GCM: it covers the timestamp and the
+    // sender's ephemeral key, neither of which AES-GCM's own tag ever sees.
+    let framed = build_framed_bytes(
+        &message_info.sender_ephemeral,
+        &message_info.nonce,
+        message_info.timestamp_ms,
+        &message_info.ciphertext,
+    );
+    let hmac_info = build_hmac_info(&message_info.sender_ephemeral, &receiver_pubkey, message_info.kem_ciphertext.as_deref());
+    let hmac_key = derive_hmac_key(&ikm, &hmac_info);
+    let mut mac = <HmacSha256 as Mac>::new_from_slice(&*hmac_key).unwrap();
+    mac.update(&framed);
+    mac.verify_slice(&message_info.hmac_tag).map_err(|_| DecryptError::Tampered)?;
+
+    // Recover the sender's identity key from the signature over the framed
+    // bytes, rather than trusting a `sender_id_pubkey` field on the wire, and
+    // check it against the caller's expectation if they supplied one.
+    let sender_pubkey = recover_pubkey(&framed, &message_info.signature, message_info.recovery_id)
+        .map_err(|_| DecryptError::Tampered)?;
+    if let Some(expected_sender) = expected_sender {
+        if sender_pubkey != expected_sender {
+            return Err(DecryptError::UnexpectedSender);
+        }
+    }
+
+    let now = now_ms();
+    if now.abs_diff(message_info.timestamp_ms) > max_skew_ms {
+        return Err(DecryptError::Stale);
+    }
+
+    let cipher_info = build_cipher_info(&message_info.sender_ephemeral, &receiver_pubkey, message_info.kem_ciphertext.as_deref());
+    let (cipher, _) = generate_cipher(&ikm, &cipher_info);
+
+    cipher
+        .decrypt(&message_info.nonce, message_info.ciphertext.as_ref())
+        .map_err(|_| DecryptError::Aead)
+}
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secret_wrappers_zeroize_on_drop() {
+        fn assert_zeroize_on_drop<T: ZeroizeOnDrop>() {}
+
+        assert_zeroize_on_drop::<SecretBytes>();
+        assert_zeroize_on_drop::<Keypair>();
+    }
+
+    #[test]
+    fn wire_serialize_deserialize_round_trip() {
+        let (pub_a, sec_a) = generate_keypair();
+        let (pub_b, sec_b) = generate_keypair();
+
+        let message_info = send_message("milady", pub_a, sec_a, pub_b);
+        let bytes = message_info.serialize();
+
+        let parsed = EncryptedMessageInfo::deserialize(&bytes, pub_b).unwrap();
+        let decrypted = decrypt_message_info(parsed, sec_b).unwrap();
+
+        assert_eq!(decrypted, b"milady");
+    }
+
+    #[test]
+    fn wire_deserialize_rejects_truncated_buffer() {
+        let (pub_a, sec_a) = generate_keypair();
+        let (pub_b, _sec_b) = generate_keypair();
+
+        let message_info = send_message("milady", pub_a, sec_a, pub_b);
+        let bytes = message_info.serialize();
+
+        assert!(matches!(
+            EncryptedMessageInfo::deserialize(&bytes[..bytes.len() - 10], pub_b),
+            Err(WireError::Truncated)
+        ));
+    }
+
+    #[test]
+    fn wire_deserialize_rejects_unknown_version() {
+        let (pub_a, sec_a) = generate_keypair();
+        let (pub_b, _sec_b) = generate_keypair();
+
+        let message_info = send_message("milady", pub_a, sec_a, pub_b);
+        let mut bytes = message_info.serialize();
+        bytes[0] = 0xff;
+
+        assert!(matches!(
+            EncryptedMessageInfo::deserialize(&bytes, pub_b),
+            Err(WireError::UnknownVersion(0xff))
+        ));
+    }
+
+    #[test]
+    fn wire_deserialize_rejects_invalid_sec1_point() {
+        let (pub_a, sec_a) = generate_keypair();
+        let (pub_b, _sec_b) = generate_keypair();
+
+        let message_info = send_message("milady", pub_a, sec_a, pub_b);
+        let mut bytes = message_info.serialize();
+        // First byte of `sender_ephemeral`'s SEC1 encoding, right after the
+        // version byte: 0xff isn't a valid compressed-point tag (0x02/0x03).
+        bytes[1] = 0xff;
+
+        assert!(matches!(
+            EncryptedMessageInfo::deserialize(&bytes, pub_b),
+            Err(WireError::InvalidPoint)
+        ));
+    }
+
+    #[test]
+    fn send_and_decrypt_round_trip() {
+        let (pub_a, sec_a) = generate_keypair();
+        let (pub_b, sec_b) = generate_keypair();
+
+        let message_info = send_message("milady", pub_a, sec_a, pub_b);
+        let decrypted = decrypt_message_info(message_info, sec_b).unwrap();
+
+        assert_eq!(decrypted, b"milady");
+    }
+
+    #[test]
+    fn decrypt_fails_for_unintended_recipient() {
+        let (pub_a, sec_a) = generate_keypair();
+        let (pub_b, _sec_b) = generate_keypair();
+        let (pub_c, sec_c) = generate_keypair();
+
+        // message_info is addressed to b, but c tries to decrypt it with their own
+        // (valid, but wrong) secret key. The ECDH shared secret c computes is simply
+        // different, and on top of that the HKDF info binds to pub_b, not pub_c.
+        let message_info = send_message("milady", pub_a, sec_a, pub_b);
+        let shared_with_wrong_party = compute_shared_secret(message_info.sender_ephemeral, &sec_c);
+        let info = build_cipher_info(&message_info.sender_ephemeral, &pub_c, None);
+        let (wrong_cipher, _) = generate_cipher(shared_with_wrong_party.raw_secret_bytes(), &info);
+
+        assert!(wrong_cipher.decrypt(&message_info.nonce, message_info.ciphertext.as_ref()).is_err());
+    }
+
+    // Unlike `decrypt_fails_for_unintended_recipient`, this holds the raw ECDH
+    // bytes *fixed* (the exact ones the real recipient, b, would derive) and
+    // varies only the recipient public key fed into `build_cipher_info`. If the
+    // HKDF info didn't actually bind to the recipient key, this would still
+    // decrypt successfully, since the raw secret alone is unchanged.
+    #[test]
+    fn cipher_info_binding_rejects_wrong_recipient_key() {
+        let (pub_a, sec_a) = generate_keypair();
+        let (pub_b, sec_b) = generate_keypair();
+        let (pub_c, _sec_c) = generate_keypair();
+
+        let message_info = send_message("milady", pub_a, sec_a, pub_b);
+        let shared = compute_shared_secret(message_info.sender_ephemeral, &sec_b);
+
+        let wrong_info = build_cipher_info(&message_info.sender_ephemeral, &pub_c, None);
+        let (wrong_cipher, _) = generate_cipher(shared.raw_secret_bytes(), &wrong_info);
+        assert!(wrong_cipher.decrypt(&message_info.nonce, message_info.ciphertext.as_ref()).is_err());
+
+        // Sanity check: the same raw secret with the *correct* recipient key
+        // bound into the info does decrypt, proving the failure above is from
+        // the info binding specifically, not some other mismatch.
+        let right_info = build_cipher_info(&message_info.sender_ephemeral, &pub_b, None);
+        let (right_cipher, _) = generate_cipher(shared.raw_secret_bytes(), &right_info);
+        assert!(right_cipher.decrypt(&message_info.nonce, message_info.ciphertext.as_ref()).is_ok());
+    }
+
+    #[test]
+    fn decrypt_rejects_mismatched_receiver_pubkey() {
+        let (pub_a, sec_a) = generate_keypair();
+        let (pub_b, sec_b) = generate_keypair();
+        let (pub_c, _sec_c) = generate_keypair();
+
+        let bytes = send_message("milady", pub_a, sec_a, pub_b).serialize();
+        // Deserialize with the wrong recipient key (c's, not b's); decrypting
+        // with b's actual secret key should be rejected rather than silently
+        // proceeding with the mismatched stored value.
+        let message_info = EncryptedMessageInfo::deserialize(&bytes, pub_c).unwrap();
+
+        assert!(matches!(
+            decrypt_message_info(message_info, sec_b),
+            Err(DecryptError::WrongReceiver)
+        ));
+    }
+
+    #[test]
+    fn decrypt_rejects_stale_timestamp() {
+        let (pub_a, sec_a) = generate_keypair();
+        let (pub_b, sec_b) = generate_keypair();
+
+        let stale_timestamp = now_ms() - (DEFAULT_FRESHNESS_WINDOW_MS * 2);
+        let message_info = send_message_at("milady", pub_a, sec_a, pub_b, stale_timestamp);
+
+        assert!(matches!(
+            decrypt_message_info(message_info, sec_b),
+            Err(DecryptError::Stale)
+        ));
+    }
+
+    #[test]
+    fn decrypt_rejects_tampered_hmac_tag() {
+        let (pub_a, sec_a) = generate_keypair();
+        let (pub_b, sec_b) = generate_keypair();
+
+        let mut message_info = send_message("milady", pub_a, sec_a, pub_b);
+        message_info.hmac_tag[0] ^= 0xff;
+
+        assert!(matches!(
+            decrypt_message_info(message_info, sec_b),
+            Err(DecryptError::Tampered)
+        ));
+    }
+
+    #[test]
+    fn recovered_signer_matches_original() {
+        let (pub_a, sec_a) = generate_keypair();
+
+        let message = b"milady";
+        let (signature, recovery_id) = sign_recoverable(&sec_a, message);
+
+        assert_eq!(recover_pubkey(message, &signature, recovery_id).unwrap(), pub_a);
+    }
+
+    #[test]
+    fn tampered_message_recovers_different_signer() {
+        let (pub_a, sec_a) = generate_keypair();
+
+        let message = b"milady";
+        let (signature, recovery_id) = sign_recoverable(&sec_a, message);
+
+        let recovered = recover_pubkey(b"tampered", &signature, recovery_id).unwrap();
+        assert_ne!(recovered, pub_a);
+    }
+
+    #[test]
+    fn decrypt_from_rejects_unexpected_sender() {
+        let (pub_a, sec_a) = generate_keypair();
+        let (pub_b, sec_b) = generate_keypair();
+        let (impostor_pub, _impostor_sec) = generate_keypair();
+
+        let message_info = send_message("milady", pub_a, sec_a, pub_b);
+
+        assert!(matches!(
+            decrypt_message_info_from(message_info, sec_b, impostor_pub),
+            Err(DecryptError::UnexpectedSender)
+        ));
+    }
+
+    #[test]
+    fn decrypt_from_accepts_expected_sender() {
+        let (pub_a, sec_a) = generate_keypair();
+        let (pub_b, sec_b) = generate_keypair();
+
+        let message_info = send_message("milady", pub_a, sec_a, pub_b);
+        let decrypted = decrypt_message_info_from(message_info, sec_b, pub_a).unwrap();
+
+        assert_eq!(decrypted, b"milady");
+    }
+
+    #[cfg(feature = "pq-hybrid")]
+    #[test]
+    fn hybrid_send_and_decrypt_round_trip() {
+        let (pub_a, sec_a) = generate_keypair();
+        let (pub_b, sec_b) = generate_keypair();
+        let kem_keypair = hybrid::KemKeypair::generate();
+
+        let message_info = send_message_hybrid("milady", pub_a, sec_a, pub_b, &kem_keypair.encapsulation_key);
+        let decrypted = decrypt_message_info_hybrid(message_info, sec_b, &kem_keypair.decapsulation_key).unwrap();
+
+        assert_eq!(decrypted, b"milady");
+    }
+
+    #[cfg(feature = "pq-hybrid")]
+    #[test]
+    fn hybrid_corrupted_kem_ciphertext_fails_to_decrypt() {
+        let (pub_a, sec_a) = generate_keypair();
+        let (pub_b, sec_b) = generate_keypair();
+        let kem_keypair = hybrid::KemKeypair::generate();
+
+        let mut message_info = send_message_hybrid("milady", pub_a, sec_a, pub_b, &kem_keypair.encapsulation_key);
+        message_info.kem_ciphertext.as_mut().unwrap()[0] ^= 0xff;
+
+        assert!(decrypt_message_info_hybrid(message_info, sec_b, &kem_keypair.decapsulation_key).is_err());
+    }
 }
\ No newline at end of file
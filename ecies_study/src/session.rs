@@ -0,0 +1,328 @@
+//! Interactive, mutually-authenticated secure channel over an async byte
+//! stream, gated behind the `async-session` feature. Extends the crate's
+//! single-shot `send_message` into a long-lived channel: a one-time ECDH
+//! handshake derives a *pair* of `Aes256Gcm` ciphers, one per direction (like
+//! TLS's separate client/server write keys), then [`Session::send`]/
+//! [`Session::recv`] exchange any number of records authenticated under the
+//! cipher for that direction. Nonces are derived from a monotonic per-direction
+//! counter rather than generated at random, since a long-lived connection sends
+//! enough records that random-nonce collision risk stops being negligible.
+//! Separate directional keys are essential here: without them, both peers'
+//! counters start at 0, so their very first `send()` calls would reuse the
+//! same (key, nonce) pair under a single shared cipher.
+//!
+//! Before the ephemeral key exchange, each side also contributes a random
+//! challenge that the *other* side's identity signature must cover (see
+//! `Session::handshake`). Without this, a handshake message captured off the
+//! wire could be replayed verbatim into a brand new session at any later
+//! time -- the ephemeral key it carries is fresh, but nothing previously tied
+//! the signature to that one specific live handshake.
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use aes_gcm::{Aes256Gcm, Nonce};
+use aes_gcm::aead::{Aead, generic_array::typenum::U12};
+
+use elliptic_curve::sec1::ToEncodedPoint;
+use elliptic_curve::{PublicKey, SecretKey};
+use k256::Secp256k1;
+use k256::ecdsa::{RecoveryId, Signature};
+
+use rand_core::{OsRng, RngCore};
+
+use crate::error::SessionError;
+use crate::{
+    build_cipher_info, compute_shared_secret, generate_cipher, recover_pubkey,
+    sign_recoverable, Keypair, SEC1_COMPRESSED_LEN, SIGNATURE_LEN,
+};
+
+// Length prefix on each `send`/`recv` record: a u32 BE byte count of the
+// AES-GCM ciphertext (tag included) that follows.
+const LENGTH_PREFIX_LEN: usize = 4;
+
+// Length of the random, per-handshake freshness challenge each side sends
+// before the signed ephemeral-key exchange (see `Session::handshake`).
+const CHALLENGE_LEN: usize = 16;
+
+// Fixed size of a handshake message: a SEC1-compressed ephemeral public key,
+// a recoverable signature over it, and the signature's one-byte recovery id.
+const HANDSHAKE_MESSAGE_LEN: usize = SEC1_COMPRESSED_LEN + SIGNATURE_LEN + 1;
+
+// Direction labels mixed into each side's HKDF `info`, so the "first writer"
+// and "second writer" directions (see `Session::handshake`) get independent
+// AES keys instead of sharing one cipher across both directions.
+const FIRST_TO_SECOND_LABEL: &[u8] = b"|session-first-to-second";
+const SECOND_TO_FIRST_LABEL: &[u8] = b"|session-second-to-first";
+
+/// An established, mutually-authenticated secure channel over transport `S`.
+/// Build one with [`Session::handshake`], then exchange records with
+/// [`Session::send`]/[`Session::recv`].
+pub struct Session<S> {
+    stream: S,
+    send_cipher: Aes256Gcm,
+    recv_cipher: Aes256Gcm,
+    send_counter: u64,
+    recv_counter: u64,
+}
+
+impl<S> Session<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    /// Run the handshake over `stream`, proving our identity with
+    /// `identity_secret` and rejecting the peer unless their recovered
+    /// signer key matches `expected_peer`. `go_first` picks who writes first
+    /// at each step, so both ends of a duplex stream don't deadlock each
+    /// trying to read before writing. Regardless of `go_first`, each side
+    /// always sends its own messages before inspecting the peer's, so one
+    /// side failing to verify the other never prevents the honest side's
+    /// handshake from completing (see `verify_handshake_message`).
+    pub async fn handshake(
+        mut stream: S,
+        identity_secret: &SecretKey<Secp256k1>,
+        expected_peer: PublicKey<Secp256k1>,
+        go_first: bool,
+    ) -> Result<Self, SessionError> {
+        // Held for this function's whole lifetime (not cloned out into a bare
+        // `SecretKey`), so the ephemeral scalar stays under `Keypair`'s
+        // zeroize-on-drop guarantee until `my_eph_keypair` drops at the end.
+        let my_eph_keypair = Keypair::generate();
+        let my_eph_pub = my_eph_keypair.public_key();
+        let my_eph_bytes = my_eph_pub.to_encoded_point(true).as_bytes().to_vec();
+
+        // A fresh challenge only this run of the handshake knows about. The
+        // peer's identity signature (below) must cover *our* copy of it, so a
+        // signature captured from an earlier handshake can't be replayed here.
+        let mut my_challenge = [0u8; CHALLENGE_LEN];
+        OsRng.fill_bytes(&mut my_challenge);
+
+        let peer_challenge = if go_first {
+            stream.write_all(&my_challenge).await?;
+            read_challenge(&mut stream).await?
+        } else {
+            let peer_challenge = read_challenge(&mut stream).await?;
+            stream.write_all(&my_challenge).await?;
+            peer_challenge
+        };
+
+        // Sign the ephemeral key together with the peer's challenge: we could
+        // only have produced this exact signature after seeing that value,
+        // i.e. after this specific live handshake began.
+        let mut signed_bytes = my_eph_bytes.clone();
+        signed_bytes.extend_from_slice(&peer_challenge);
+        let (my_signature, my_recovery_id) = sign_recoverable(identity_secret, &signed_bytes);
+
+        let peer_message = if go_first {
+            write_handshake_message(&mut stream, &my_eph_bytes, &my_signature, my_recovery_id).await?;
+            read_handshake_message(&mut stream).await?
+        } else {
+            let peer_message = read_handshake_message(&mut stream).await?;
+            write_handshake_message(&mut stream, &my_eph_bytes, &my_signature, my_recovery_id).await?;
+            peer_message
+        };
+        let peer_eph_pub = verify_handshake_message(peer_message, expected_peer, &my_challenge)?;
+
+        // Both sides must derive the same HKDF `info`, so bind it by handshake
+        // order (first writer, then second writer) rather than by local role.
+        let (first_eph, second_eph) = if go_first { (my_eph_pub, peer_eph_pub) } else { (peer_eph_pub, my_eph_pub) };
+
+        let shared = compute_shared_secret(peer_eph_pub, my_eph_keypair.secret_key());
+        let base_info = build_cipher_info(&first_eph, &second_eph, None);
+
+        // Derive independent keys for each direction so the two peers' nonce
+        // counters, which both start at 0, never collide under the same key.
+        let mut first_to_second_info = base_info.clone();
+        first_to_second_info.extend_from_slice(FIRST_TO_SECOND_LABEL);
+        let (first_to_second_cipher, _) = generate_cipher(shared.raw_secret_bytes(), &first_to_second_info);
+
+        let mut second_to_first_info = base_info;
+        second_to_first_info.extend_from_slice(SECOND_TO_FIRST_LABEL);
+        let (second_to_first_cipher, _) = generate_cipher(shared.raw_secret_bytes(), &second_to_first_info);
+
+        let (send_cipher, recv_cipher) = if go_first {
+            (first_to_second_cipher, second_to_first_cipher)
+        } else {
+            (second_to_first_cipher, first_to_second_cipher)
+        };
+
+        Ok(Session { stream, send_cipher, recv_cipher, send_counter: 0, recv_counter: 0 })
+    }
+
+    /// Encrypt and send one record. Each call advances this direction's nonce
+    /// counter; it never repeats for the life of the session.
+    pub async fn send(&mut self, plaintext: &[u8]) -> Result<(), SessionError> {
+        let nonce = nonce_from_counter(self.send_counter);
+        self.send_counter = self.send_counter.checked_add(1).expect("session send counter overflowed");
+
+        let ciphertext = self.send_cipher.encrypt(&nonce, plaintext).map_err(|_| SessionError::Encrypt)?;
+
+        let len = ciphertext.len() as u32;
+        self.stream.write_all(&len.to_be_bytes()).await?;
+        self.stream.write_all(&ciphertext).await?;
+        Ok(())
+    }
+
+    /// Receive and decrypt one record, blocking until a full length-prefixed
+    /// frame has arrived.
+    pub async fn recv(&mut self) -> Result<Vec<u8>, SessionError> {
+        let mut len_bytes = [0u8; LENGTH_PREFIX_LEN];
+        self.stream.read_exact(&mut len_bytes).await?;
+        let len = u32::from_be_bytes(len_bytes) as usize;
+
+        let mut ciphertext = vec![0u8; len];
+        self.stream.read_exact(&mut ciphertext).await?;
+
+        let nonce = nonce_from_counter(self.recv_counter);
+        self.recv_counter = self.recv_counter.checked_add(1).expect("session recv counter overflowed");
+
+        self.recv_cipher.decrypt(&nonce, ciphertext.as_ref()).map_err(|_| SessionError::Decrypt)
+    }
+}
+
+// A per-direction monotonic counter, zero-extended into the low 8 bytes of
+// the 12-byte AES-GCM nonce. Unlike a random nonce, this can never repeat
+// within the ~2^64 records a single session could ever send.
+fn nonce_from_counter(counter: u64) -> Nonce<U12> {
+    let mut bytes = [0u8; 12];
+    bytes[4..].copy_from_slice(&counter.to_be_bytes());
+    *Nonce::<U12>::from_slice(&bytes)
+}
+
+async fn read_challenge<S: AsyncRead + Unpin>(stream: &mut S) -> Result<[u8; CHALLENGE_LEN], SessionError> {
+    let mut challenge = [0u8; CHALLENGE_LEN];
+    stream.read_exact(&mut challenge).await?;
+    Ok(challenge)
+}
+
+async fn write_handshake_message<S: AsyncWrite + Unpin>(
+    stream: &mut S,
+    eph_bytes: &[u8],
+    signature: &Signature,
+    recovery_id: RecoveryId,
+) -> Result<(), SessionError> {
+    stream.write_all(eph_bytes).await?;
+    stream.write_all(&signature.to_bytes()).await?;
+    stream.write_all(&[recovery_id.to_byte()]).await?;
+    Ok(())
+}
+
+// An as-yet-unverified handshake message: parsed off the wire, but not yet
+// checked against the challenge we sent or the identity we expect. Kept
+// separate from `verify_handshake_message` so a verification failure never
+// stops us from already having sent our own handshake message (see
+// `Session::handshake`).
+struct RawHandshakeMessage {
+    eph_bytes: Vec<u8>,
+    signature: Signature,
+    recovery_id: RecoveryId,
+}
+
+async fn read_handshake_message<S: AsyncRead + Unpin>(stream: &mut S) -> Result<RawHandshakeMessage, SessionError> {
+    let mut message = [0u8; HANDSHAKE_MESSAGE_LEN];
+    stream.read_exact(&mut message).await?;
+
+    let eph_bytes = message[..SEC1_COMPRESSED_LEN].to_vec();
+    let signature_bytes = &message[SEC1_COMPRESSED_LEN..SEC1_COMPRESSED_LEN + SIGNATURE_LEN];
+    let recovery_id_byte = message[SEC1_COMPRESSED_LEN + SIGNATURE_LEN];
+
+    let signature = Signature::from_slice(signature_bytes).map_err(|_| SessionError::Handshake)?;
+    let recovery_id = RecoveryId::from_byte(recovery_id_byte).ok_or(SessionError::Handshake)?;
+
+    Ok(RawHandshakeMessage { eph_bytes, signature, recovery_id })
+}
+
+// Check that `message`'s signature recovers to `expected_peer` over
+// (ephemeral key || our own challenge) -- the same bytes the peer was
+// supposed to sign in `Session::handshake` -- and that the ephemeral key
+// itself parses as a valid SEC1 point.
+fn verify_handshake_message(
+    message: RawHandshakeMessage,
+    expected_peer: PublicKey<Secp256k1>,
+    my_challenge: &[u8; CHALLENGE_LEN],
+) -> Result<PublicKey<Secp256k1>, SessionError> {
+    let eph_pub = PublicKey::<Secp256k1>::from_sec1_bytes(&message.eph_bytes).map_err(|_| SessionError::Handshake)?;
+
+    let mut signed_bytes = message.eph_bytes;
+    signed_bytes.extend_from_slice(my_challenge);
+
+    let signer = recover_pubkey(&signed_bytes, &message.signature, message.recovery_id)
+        .map_err(|_| SessionError::Handshake)?;
+    if signer != expected_peer {
+        return Err(SessionError::Handshake);
+    }
+
+    Ok(eph_pub)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate_keypair;
+
+    #[tokio::test]
+    async fn loopback_session_round_trip() {
+        let (pub_a, sec_a) = generate_keypair();
+        let (pub_b, sec_b) = generate_keypair();
+
+        let (stream_a, stream_b) = tokio::io::duplex(4096);
+
+        let (session_a, session_b) = tokio::join!(
+            Session::handshake(stream_a, &sec_a, pub_b, true),
+            Session::handshake(stream_b, &sec_b, pub_a, false),
+        );
+        let mut session_a = session_a.unwrap();
+        let mut session_b = session_b.unwrap();
+
+        session_a.send(b"milady").await.unwrap();
+        assert_eq!(session_b.recv().await.unwrap(), b"milady");
+
+        session_b.send(b"gm").await.unwrap();
+        assert_eq!(session_a.recv().await.unwrap(), b"gm");
+    }
+
+    #[tokio::test]
+    async fn first_message_each_direction_does_not_reuse_nonce_under_same_key() {
+        let (pub_a, sec_a) = generate_keypair();
+        let (pub_b, sec_b) = generate_keypair();
+
+        let (stream_a, stream_b) = tokio::io::duplex(4096);
+
+        let (session_a, session_b) = tokio::join!(
+            Session::handshake(stream_a, &sec_a, pub_b, true),
+            Session::handshake(stream_b, &sec_b, pub_a, false),
+        );
+        let mut session_a = session_a.unwrap();
+        let mut session_b = session_b.unwrap();
+
+        // Both directions' counters start at 0. If send/recv shared a single
+        // cipher, these two first messages would be AES-GCM-encrypted under
+        // the identical (key, nonce) pair.
+        assert_ne!(session_a.send_cipher.encrypt(&nonce_from_counter(0), b"milady".as_slice()).unwrap(),
+            session_b.send_cipher.encrypt(&nonce_from_counter(0), b"milady".as_slice()).unwrap());
+
+        session_a.send(b"from a").await.unwrap();
+        session_b.send(b"from b").await.unwrap();
+
+        assert_eq!(session_b.recv().await.unwrap(), b"from a");
+        assert_eq!(session_a.recv().await.unwrap(), b"from b");
+    }
+
+    #[tokio::test]
+    async fn handshake_rejects_unexpected_peer() {
+        let (_pub_a, sec_a) = generate_keypair();
+        let (pub_b, sec_b) = generate_keypair();
+        let (impostor_pub, _impostor_sec) = generate_keypair();
+
+        let (stream_a, stream_b) = tokio::io::duplex(4096);
+
+        // b expects `impostor_pub`, but a authenticates as its real identity,
+        // so b's handshake should reject it.
+        let (session_a, session_b) = tokio::join!(
+            Session::handshake(stream_a, &sec_a, pub_b, true),
+            Session::handshake(stream_b, &sec_b, impostor_pub, false),
+        );
+
+        assert!(session_a.is_ok());
+        assert!(matches!(session_b, Err(SessionError::Handshake)));
+    }
+}